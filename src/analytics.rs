@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::warn;
+use tokio::sync::mpsc;
+
+use crate::storage::Storage;
+
+/// A single resolved redirect, handed off to the background aggregator. The
+/// timestamp is captured on the hot path but is not read by the aggregator,
+/// which batches on the pending count and a fixed interval; it leaves room for
+/// time-bucketed stats later.
+pub struct HitEvent {
+    pub id: String,
+    pub at: Instant,
+}
+
+/// Flush the buffer once it reaches this many pending increments...
+const FLUSH_EVERY: usize = 256;
+/// ...or at least this often, so low-traffic links still get persisted.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Spawn the background aggregation task and return the sender side of the
+/// channel. `handle_redirect` pushes a [`HitEvent`] after a successful lookup
+/// and returns immediately; this task batches increments and flushes them into
+/// the backend without touching the redirect latency.
+pub fn spawn(storage: Arc<dyn Storage>) -> mpsc::Sender<HitEvent> {
+    let (tx, mut rx) = mpsc::channel::<HitEvent>(1024);
+    tokio::spawn(async move {
+        let mut pending: HashMap<String, u64> = HashMap::new();
+        let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            tokio::select! {
+                maybe = rx.recv() => match maybe {
+                    Some(event) => {
+                        *pending.entry(event.id).or_insert(0) += 1;
+                        if pending.values().sum::<u64>() as usize >= FLUSH_EVERY {
+                            flush(&storage, &mut pending).await;
+                        }
+                    }
+                    // Senders dropped: flush the tail and stop.
+                    None => {
+                        flush(&storage, &mut pending).await;
+                        break;
+                    }
+                },
+                _ = ticker.tick() => flush(&storage, &mut pending).await,
+            }
+        }
+    });
+    tx
+}
+
+async fn flush(storage: &Arc<dyn Storage>, pending: &mut HashMap<String, u64>) {
+    for (id, count) in pending.drain() {
+        // One write per id per flush, not one per hit -- that's the point of
+        // batching.
+        if let Err(e) = storage.record_hits(&id, count).await {
+            warn!("failed to record {count} hit(s) for {id}: {e:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{RedirectKind, RedirectRecord, Result, Storage};
+    use std::sync::Mutex;
+
+    /// Records every `record_hits` call instead of persisting anything, so
+    /// tests can assert on how many writes `flush` issued per id.
+    #[derive(Default)]
+    struct RecordingStorage {
+        calls: Mutex<Vec<(String, u64)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Storage for RecordingStorage {
+        async fn lookup(&self, _id: &str) -> Result<(String, RedirectKind)> {
+            unimplemented!("not exercised by the flush test")
+        }
+        async fn store(&self, _id: &str, _url: &str, _owner: &str, _kind: RedirectKind) -> Result<()> {
+            unimplemented!("not exercised by the flush test")
+        }
+        async fn list_by_owner(&self, _owner: &str) -> Result<Vec<(String, String)>> {
+            unimplemented!("not exercised by the flush test")
+        }
+        async fn record_hits(&self, id: &str, n: u64) -> Result<()> {
+            self.calls.lock().unwrap().push((id.to_string(), n));
+            Ok(())
+        }
+        async fn stats(&self, _id: &str) -> Result<u64> {
+            unimplemented!("not exercised by the flush test")
+        }
+        async fn iter_all(&self) -> Result<Vec<RedirectRecord>> {
+            unimplemented!("not exercised by the flush test")
+        }
+        async fn set_import_cursor(&self, _cursor: Option<&str>) -> Result<()> {
+            unimplemented!("not exercised by the flush test")
+        }
+        async fn import_cursor(&self) -> Result<Option<String>> {
+            unimplemented!("not exercised by the flush test")
+        }
+    }
+
+    #[tokio::test]
+    async fn flush_issues_one_batched_write_per_id() {
+        let recording = Arc::new(RecordingStorage::default());
+        let storage: Arc<dyn Storage> = recording.clone();
+
+        let mut pending = HashMap::new();
+        pending.insert("abc".to_string(), 3u64);
+        pending.insert("xyz".to_string(), 1u64);
+
+        flush(&storage, &mut pending).await;
+
+        assert!(pending.is_empty());
+        let mut calls = recording.calls.lock().unwrap().clone();
+        calls.sort();
+        assert_eq!(calls, vec![("abc".to_string(), 3), ("xyz".to_string(), 1)]);
+    }
+}
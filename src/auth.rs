@@ -0,0 +1,136 @@
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use poem::{http::StatusCode, FromRequest, Request, RequestBody, Result as PoemResult};
+use serde::{Deserialize, Serialize};
+
+/// JWT claims issued by `/login` and verified on protected routes. `sub`
+/// carries the owner identity; `exp` is a Unix timestamp after which the
+/// token is rejected.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: usize,
+}
+
+/// HS256 signing key derived from the server secret. Stored in `Data` so
+/// handlers and extractors share a single instance.
+#[derive(Clone)]
+pub struct Keys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+}
+
+impl Keys {
+    pub fn new(secret: &[u8]) -> Self {
+        Keys {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+        }
+    }
+
+    /// Load the signing secret from `JWT_SECRET`, falling back to a dev-only
+    /// default so the server still boots without configuration.
+    pub fn from_env() -> Self {
+        let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "dev-secret".to_string());
+        Keys::new(secret.as_bytes())
+    }
+
+    /// Issue a token for `owner` valid for `ttl_secs` seconds.
+    pub fn issue(&self, owner: &str, ttl_secs: u64) -> Result<String, jsonwebtoken::errors::Error> {
+        let exp = now() + ttl_secs as usize;
+        let claims = Claims {
+            sub: owner.to_string(),
+            exp,
+        };
+        encode(&Header::default(), &claims, &self.encoding)
+    }
+
+    fn verify(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+        // `Validation::default()` ships with a 60-second leeway on `exp`,
+        // which would let a token outlive the TTL `/login` advertised.
+        // Reject right at `exp` instead.
+        let mut validation = Validation::default();
+        validation.leeway = 0;
+        decode::<Claims>(token, &self.decoding, &validation).map(|d| d.claims)
+    }
+}
+
+fn now() -> usize {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as usize)
+        .unwrap_or(0)
+}
+
+/// Extractor that resolves the authenticated owner from a
+/// `Authorization: Bearer <token>` header. A missing, malformed, or expired
+/// token yields `401 Unauthorized`.
+pub struct AuthOwner(pub String);
+
+impl<'a> FromRequest<'a> for AuthOwner {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> PoemResult<Self> {
+        let keys = req
+            .data::<Keys>()
+            .ok_or_else(|| poem::Error::from_status(StatusCode::INTERNAL_SERVER_ERROR))?;
+        let token = req
+            .headers()
+            .get("Authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| poem::Error::from_status(StatusCode::UNAUTHORIZED))?;
+        let claims = keys
+            .verify(token)
+            .map_err(|_| poem::Error::from_status(StatusCode::UNAUTHORIZED))?;
+        Ok(AuthOwner(claims.sub))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poem::{Body, RequestBuilder};
+
+    fn request(keys: Keys, bearer: Option<&str>) -> Request {
+        let mut builder: RequestBuilder = Request::builder().extension(keys);
+        if let Some(token) = bearer {
+            builder = builder.header("Authorization", format!("Bearer {token}"));
+        }
+        builder.finish()
+    }
+
+    #[test]
+    fn issues_a_token_that_verifies_back_to_the_same_owner() {
+        let keys = Keys::new(b"test-secret");
+        let token = keys.issue("alice", 3600).expect("issue");
+        let claims = keys.verify(&token).expect("verify");
+        assert_eq!(claims.sub, "alice");
+    }
+
+    #[test]
+    fn rejects_an_expired_token() {
+        let keys = Keys::new(b"test-secret");
+        let token = keys.issue("alice", 0).expect("issue");
+        // `exp` equals `now` at issuance, and `verify` uses zero leeway, so
+        // stepping even one second past issuance is enough to expire it.
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        assert!(keys.verify(&token).is_err());
+    }
+
+    #[tokio::test]
+    async fn missing_token_is_unauthorized() {
+        let keys = Keys::new(b"test-secret");
+        let req = request(keys, None);
+        let mut body = RequestBody::new(Body::empty());
+        let err = AuthOwner::from_request(&req, &mut body).await.unwrap_err();
+        assert_eq!(err.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn valid_token_resolves_the_owner() {
+        let keys = Keys::new(b"test-secret");
+        let token = keys.issue("alice", 3600).expect("issue");
+        let req = request(keys, Some(&token));
+        let mut body = RequestBody::new(Body::empty());
+        let AuthOwner(owner) = AuthOwner::from_request(&req, &mut body).await.expect("authorized");
+        assert_eq!(owner, "alice");
+    }
+}
@@ -1,131 +1,374 @@
+use handlebars::Handlebars;
 use poem::{
     get, handler, post,
     middleware::AddData,
-    web::{Form, Html, Redirect, Data, Path},
+    web::{Form, Html, Json, Redirect, Data, Path},
     EndpointExt, IntoResponse, Response, Route, Server,
 };
-use serde::Deserialize;
-use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use log::debug;
 
+mod analytics;
+mod auth;
+mod storage;
+mod templates;
+use analytics::HitEvent;
+use auth::{AuthOwner, Keys};
+use tokio::sync::mpsc;
+use storage::{RedirectKind, RedirectRecord, Storage, StorageError};
+use templates::{ErrorContext, IndexContext, RedirectEntry, RedirectsContext};
 
-pub type Result<T> = std::result::Result<T, StorageError>;
-
-#[derive(Debug)]
-pub enum StorageError {
-    NotFound,
-    AlreadyExists,
-    InternalError(String),
-}
-
-#[async_trait]
-pub trait Storage: Send + Sync {
-    async fn lookup(&self, id: &str) -> Result<String>;
-    async fn store(&self, id: &str, url: &str, owner: &str) -> Result<()>;
-}
+/// Import records in batches of this size, persisting a continuation cursor
+/// after each chunk so an interrupted import can resume from where it
+/// stopped.
+const IMPORT_CHUNK_SIZE: usize = 5000;
 
 #[derive(Deserialize)]
 struct CreateRedirectRequest {
     short_name: String,
     url: String,
+    // Filled in from the verified JWT `sub` claim, never trusted from the form.
+    #[serde(default)]
     owner: String,
+    // HTTP semantics of the redirect; defaults to `permanent` (308).
+    #[serde(default)]
+    redirect_kind: RedirectKind,
+}
+
+#[derive(Deserialize)]
+struct LoginRequest {
+    owner: String,
+}
+
+/// Issue a bearer token for the given owner. In a real deployment this would
+/// sit behind a credential check; here it simply mints a token so the owner
+/// can authenticate subsequent requests.
+#[handler]
+async fn login(
+    Form(payload): Form<LoginRequest>,
+    keys: Data<&Keys>,
+) -> Response {
+    match keys.issue(&payload.owner, 3600) {
+        Ok(token) => token.into_response(),
+        Err(_) => poem::http::StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
 }
 
 #[handler]
 async fn create_redirect(
-    Form(payload): Form<CreateRedirectRequest>,
-    storage: Data<&Arc<InMemoryStorage>>,
-) -> impl IntoResponse {
-    let result = storage.store(&payload.short_name, &payload.url, &payload.owner).await;
-    
-    let (message, is_success) = match result {
+    AuthOwner(owner): AuthOwner,
+    Form(mut payload): Form<CreateRedirectRequest>,
+    storage: Data<&Arc<dyn Storage>>,
+    hb: Data<&Arc<Handlebars<'static>>>,
+) -> Response {
+    // The owner is authoritative from the token, not the submitted form.
+    payload.owner = owner;
+    let result = storage
+        .store(&payload.short_name, &payload.url, &payload.owner, payload.redirect_kind)
+        .await;
+
+    let (message, success) = match result {
         Ok(_) => ("Redirect created successfully".to_string(), true),
         Err(StorageError::AlreadyExists) => ("ID already exists".to_string(), false),
         Err(_) => ("An error occurred while creating the redirect".to_string(), false),
     };
 
-    let mut html = include_str!("templates/index.html").to_string();
-    
-    if !message.is_empty() {
-        html = html.replace(
-            "<!-- MESSAGE_PLACEHOLDER -->", 
-            &format!("<div class='message {}' style='display:block;'>{}</div>", 
-                     if is_success { "success" } else { "error" }, 
-                     message)
-        );
+    render_index(&hb, Some(message), success)
+}
+
+#[handler]
+async fn index(hb: Data<&Arc<Handlebars<'static>>>) -> Response {
+    render_index(&hb, None, true)
+}
+
+/// Render the `index` template; shared by the plain landing page and the
+/// post-`/create` confirmation/error banner.
+fn render_index(hb: &Handlebars<'static>, message: Option<String>, success: bool) -> Response {
+    let context = IndexContext { message, success };
+    match hb.render("index", &context) {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => render_error(hb, poem::http::StatusCode::INTERNAL_SERVER_ERROR, format!("template error: {e}")),
     }
+}
 
-    Html(html)
+/// Render the `error` template at the given status, used as the fallback
+/// page whenever a storage failure or unmatched route would otherwise need
+/// an ad-hoc response. The caller supplies `status` so genuine client errors
+/// (401, 404, ...) are preserved rather than flattened to a 500.
+fn render_error(hb: &Handlebars<'static>, status: poem::http::StatusCode, message: String) -> Response {
+    let context = ErrorContext { message };
+    match hb.render("error", &context) {
+        Ok(html) => Html(html).with_status(status).into_response(),
+        // The error template itself failed to render; fall back to plain text
+        // rather than recursing.
+        Err(e) => (poem::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
 }
 
+/// List the redirects owned by the authenticated caller.
 #[handler]
-async fn index() -> impl IntoResponse {
-    let html = include_str!("templates/index.html")
-        .replace("<!-- MESSAGE_PLACEHOLDER -->", "");
-    
-    Html(html)
+async fn list_redirects(
+    AuthOwner(owner): AuthOwner,
+    storage: Data<&Arc<dyn Storage>>,
+    hb: Data<&Arc<Handlebars<'static>>>,
+) -> Response {
+    match storage.list_by_owner(&owner).await {
+        Ok(entries) => {
+            let context = RedirectsContext {
+                redirects: entries
+                    .into_iter()
+                    .map(|(short_name, url)| RedirectEntry { short_name, url })
+                    .collect(),
+            };
+            match hb.render("redirects", &context) {
+                Ok(html) => Html(html).into_response(),
+                Err(e) => render_error(&hb, poem::http::StatusCode::INTERNAL_SERVER_ERROR, format!("template error: {e}")),
+            }
+        }
+        Err(_) => render_error(
+            &hb,
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+            "An error occurred while listing your redirects".to_string(),
+        ),
+    }
 }
 
+/// Return the accumulated click count for a short link.
 #[handler]
-async fn handle_redirect(
+async fn stats(
     Path(id): Path<String>,
-    storage: Data<&Arc<InMemoryStorage>>,
+    storage: Data<&Arc<dyn Storage>>,
 ) -> Response {
-    debug!("Looked up {}", id.as_str());
-    match storage.lookup(&id).await {
-        Ok(url) => Redirect::permanent(url).into_response(),
-        Err(StorageError::NotFound) => Redirect::temporary("/?message=Redirect not found&success=false").into_response(),
-        Err(_) => Redirect::temporary("/?message=An error occurred while looking up the redirect&success=false").into_response(),
+    match storage.stats(&id).await {
+        Ok(count) => count.to_string().into_response(),
+        Err(_) => poem::http::StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     }
 }
 
-// Example in-memory storage implementation
-struct InMemoryStorage {
-    data: std::sync::RwLock<std::collections::HashMap<String, (String, String)>>,
+/// Stream every stored redirect as newline-delimited JSON, one
+/// [`RedirectRecord`] per line. Requires a valid bearer token -- this is an
+/// operator migration tool, not a public endpoint, and the export otherwise
+/// leaks every owner's redirects to anyone who asks.
+#[handler]
+async fn export(AuthOwner(_owner): AuthOwner, storage: Data<&Arc<dyn Storage>>) -> Response {
+    match storage.iter_all().await {
+        Ok(records) => {
+            let body = records
+                .iter()
+                .filter_map(|r| serde_json::to_string(r).ok())
+                .collect::<Vec<_>>()
+                .join("\n");
+            body.into_response()
+        }
+        Err(_) => poem::http::StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
 }
 
-impl InMemoryStorage {
-    fn new() -> Self {
-        InMemoryStorage {
-            data: std::sync::RwLock::new(std::collections::HashMap::new()),
+#[derive(Serialize)]
+struct ImportReport {
+    inserted: u64,
+    skipped: u64,
+    failed: u64,
+}
+
+/// Returns `true` if `short_name` falls at-or-before the resume cursor and
+/// was already handled by a prior import attempt. `iter_all`/export sort by
+/// `short_name`, so a lexicographic comparison is enough to tell; once a
+/// record strictly past the cursor is seen, `resume_after` is cleared so
+/// every later record in this run is processed normally.
+fn skip_for_resume(short_name: &str, resume_after: &mut Option<String>) -> bool {
+    match resume_after {
+        Some(cursor) if short_name <= cursor.as_str() => true,
+        Some(_) => {
+            *resume_after = None;
+            false
         }
+        None => false,
     }
 }
 
-#[async_trait]
-impl Storage for InMemoryStorage {
-    async fn lookup(&self, id: &str) -> Result<String> {
-        let data = self.data.read().map_err(|e| StorageError::InternalError(e.to_string()))?;
-        data.get(id)
-            .map(|(url, _)| url.clone())
-            .ok_or(StorageError::NotFound)
-    }
+/// Bulk-load newline-delimited [`RedirectRecord`] JSON. Processes the stream
+/// in chunks of [`IMPORT_CHUNK_SIZE`], persisting a continuation cursor after
+/// each chunk so a restarted import skips records already handled. A
+/// per-record `AlreadyExists` is tallied as skipped rather than aborting the
+/// batch; malformed lines are tallied as failed. Requires a valid bearer
+/// token so only an authenticated caller may run a migration, but the token
+/// only gates *who* may import -- each record keeps the `owner` it carries
+/// in the uploaded JSON, so a full-instance `/export` followed by `/import`
+/// preserves original ownership instead of reassigning everything to
+/// whoever happened to run the import.
+#[handler]
+async fn import(AuthOwner(_owner): AuthOwner, body: String, storage: Data<&Arc<dyn Storage>>) -> Response {
+    let mut resume_after = match storage.import_cursor().await {
+        Ok(cursor) => cursor,
+        Err(_) => return poem::http::StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    let mut inserted = 0u64;
+    let mut skipped = 0u64;
+    let mut failed = 0u64;
+    let mut since_cursor = 0usize;
+
+    for line in body.lines().filter(|l| !l.trim().is_empty()) {
+        let record: RedirectRecord = match serde_json::from_str(line) {
+            Ok(record) => record,
+            Err(_) => {
+                failed += 1;
+                continue;
+            }
+        };
+
+        if skip_for_resume(&record.short_name, &mut resume_after) {
+            continue;
+        }
 
-    async fn store(&self, id: &str, url: &str, owner: &str) -> Result<()> {
-        let mut data = self.data.write().map_err(|e| StorageError::InternalError(e.to_string()))?;
-        if data.contains_key(id) {
-            Err(StorageError::AlreadyExists)
-        } else {
-            data.insert(id.to_string(), (url.to_string(), owner.to_string()));
-            Ok(())
+        match storage
+            .store(&record.short_name, &record.url, &record.owner, record.redirect_kind)
+            .await
+        {
+            Ok(()) => inserted += 1,
+            Err(StorageError::AlreadyExists) => skipped += 1,
+            Err(_) => {
+                failed += 1;
+                continue;
+            }
+        }
+
+        since_cursor += 1;
+        if since_cursor >= IMPORT_CHUNK_SIZE {
+            if storage.set_import_cursor(Some(&record.short_name)).await.is_err() {
+                return poem::http::StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+            since_cursor = 0;
         }
     }
+
+    let _ = storage.set_import_cursor(None).await;
+    Json(ImportReport { inserted, skipped, failed }).into_response()
 }
 
+/// Map a stored [`RedirectKind`] onto the `poem` redirect constructor with
+/// the matching HTTP status.
+fn redirect_for(kind: RedirectKind, url: String) -> Redirect {
+    match kind {
+        RedirectKind::Moved => Redirect::moved_permanent(url),
+        RedirectKind::Permanent => Redirect::permanent(url),
+        RedirectKind::Temporary => Redirect::temporary(url),
+        RedirectKind::SeeOther => Redirect::see_other(url),
+    }
+}
+
+#[handler]
+async fn handle_redirect(
+    Path(id): Path<String>,
+    storage: Data<&Arc<dyn Storage>>,
+    hits: Data<&mpsc::Sender<HitEvent>>,
+    hb: Data<&Arc<Handlebars<'static>>>,
+) -> Response {
+    debug!("Looked up {}", id.as_str());
+    match storage.lookup(&id).await {
+        Ok((url, kind)) => {
+            // Fire-and-forget the hit so the redirect stays off the write path.
+            let _ = hits.try_send(HitEvent {
+                id: id.clone(),
+                at: std::time::Instant::now(),
+            });
+            redirect_for(kind, url).into_response()
+        }
+        Err(StorageError::NotFound) => Redirect::temporary("/?message=Redirect not found&success=false").into_response(),
+        Err(_) => render_error(
+            &hb,
+            poem::http::StatusCode::INTERNAL_SERVER_ERROR,
+            "An error occurred while looking up the redirect".to_string(),
+        ),
+    }
+}
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
     env_logger::init();
-    let storage = Arc::new(InMemoryStorage::new());
+
+    // Backend is chosen at startup via `--backend <name>` or the
+    // `REDIRECTOR_BACKEND` env var, falling back to the in-memory backend.
+    let backend = std::env::args()
+        .skip_while(|a| a != "--backend")
+        .nth(1)
+        .or_else(|| std::env::var("REDIRECTOR_BACKEND").ok())
+        .unwrap_or_else(|| "memory".to_string());
+
+    let storage: Arc<dyn Storage> = storage::from_backend(&backend)
+        .await
+        .unwrap_or_else(|e| panic!("failed to initialize storage backend {backend:?}: {e:?}"));
+
+    let keys = Keys::from_env();
+    let hits = analytics::spawn(storage.clone());
+    let hb = Arc::new(templates::registry());
+    let catch_all_hb = hb.clone();
+
     let app = Route::new()
         .at("/", get(index))
+        .at("/login", post(login))
         .at("/create", post(create_redirect))
+        .at("/redirects", get(list_redirects))
+        .at("/stats/:id", get(stats))
+        .at("/export", get(export))
+        .at("/import", post(import))
         .at("/go/:id", get(handle_redirect))
-        .with(AddData::new(storage));
+        .with(AddData::new(storage))
+        .with(AddData::new(keys))
+        .with(AddData::new(hits))
+        .with(AddData::new(hb))
+        // Anything that falls through unmatched or rejected (a 404, or a 401
+        // from the `AuthOwner` extractor) gets the same rendered error page,
+        // instead of poem's default -- but keeps the error's own status so a
+        // missing/expired bearer token still surfaces as 401, not 500.
+        .catch_all_error(move |err: poem::Error| {
+            let hb = catch_all_hb.clone();
+            async move { render_error(&hb, err.status(), err.to_string()) }
+        });
 
     println!("Server starting on http://localhost:3000");
     Server::new(poem::listener::TcpListener::bind("127.0.0.1:3000"))
         .run(app)
         .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirect_for_dispatches_to_the_matching_status_code() {
+        let cases = [
+            (RedirectKind::Moved, poem::http::StatusCode::MOVED_PERMANENTLY),
+            (RedirectKind::Permanent, poem::http::StatusCode::PERMANENT_REDIRECT),
+            (RedirectKind::Temporary, poem::http::StatusCode::TEMPORARY_REDIRECT),
+            (RedirectKind::SeeOther, poem::http::StatusCode::SEE_OTHER),
+        ];
+        for (kind, expected) in cases {
+            let response = redirect_for(kind, "https://example.com".to_string()).into_response();
+            assert_eq!(response.status(), expected);
+        }
+    }
+
+    #[test]
+    fn skip_for_resume_skips_up_to_and_including_the_cursor() {
+        let mut resume_after = Some("b".to_string());
+        assert!(skip_for_resume("a", &mut resume_after));
+        assert!(skip_for_resume("b", &mut resume_after));
+        assert!(!skip_for_resume("c", &mut resume_after));
+        // Once a record past the cursor is seen, the cursor is cleared so
+        // later records aren't skipped even if they'd otherwise compare
+        // at-or-before some earlier value.
+        assert!(resume_after.is_none());
+        assert!(!skip_for_resume("a", &mut resume_after));
+    }
+
+    #[test]
+    fn skip_for_resume_skips_nothing_without_a_cursor() {
+        let mut resume_after = None;
+        assert!(!skip_for_resume("a", &mut resume_after));
+        assert!(resume_after.is_none());
+    }
+}
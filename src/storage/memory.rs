@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+
+use super::{RedirectKind, RedirectRecord, Result, Storage, StorageError};
+
+// Example in-memory storage implementation
+pub struct InMemoryStorage {
+    data: std::sync::RwLock<std::collections::HashMap<String, (String, String, RedirectKind)>>,
+    hits: std::sync::RwLock<std::collections::HashMap<String, u64>>,
+    cursor: std::sync::RwLock<Option<String>>,
+}
+
+impl InMemoryStorage {
+    pub fn new() -> Self {
+        InMemoryStorage {
+            data: std::sync::RwLock::new(std::collections::HashMap::new()),
+            hits: std::sync::RwLock::new(std::collections::HashMap::new()),
+            cursor: std::sync::RwLock::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for InMemoryStorage {
+    async fn lookup(&self, id: &str) -> Result<(String, RedirectKind)> {
+        let data = self.data.read().map_err(|e| StorageError::InternalError(e.to_string()))?;
+        data.get(id)
+            .map(|(url, _, kind)| (url.clone(), *kind))
+            .ok_or(StorageError::NotFound)
+    }
+
+    async fn store(&self, id: &str, url: &str, owner: &str, kind: RedirectKind) -> Result<()> {
+        let mut data = self.data.write().map_err(|e| StorageError::InternalError(e.to_string()))?;
+        if data.contains_key(id) {
+            Err(StorageError::AlreadyExists)
+        } else {
+            data.insert(id.to_string(), (url.to_string(), owner.to_string(), kind));
+            Ok(())
+        }
+    }
+
+    async fn list_by_owner(&self, owner: &str) -> Result<Vec<(String, String)>> {
+        let data = self.data.read().map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(data
+            .iter()
+            .filter(|(_, (_, o, _))| o == owner)
+            .map(|(id, (url, _, _))| (id.clone(), url.clone()))
+            .collect())
+    }
+
+    async fn record_hits(&self, id: &str, n: u64) -> Result<()> {
+        let mut hits = self.hits.write().map_err(|e| StorageError::InternalError(e.to_string()))?;
+        *hits.entry(id.to_string()).or_insert(0) += n;
+        Ok(())
+    }
+
+    async fn stats(&self, id: &str) -> Result<u64> {
+        let hits = self.hits.read().map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(hits.get(id).copied().unwrap_or(0))
+    }
+
+    async fn iter_all(&self) -> Result<Vec<RedirectRecord>> {
+        let data = self.data.read().map_err(|e| StorageError::InternalError(e.to_string()))?;
+        let mut records: Vec<RedirectRecord> = data
+            .iter()
+            .map(|(id, (url, owner, kind))| RedirectRecord {
+                short_name: id.clone(),
+                url: url.clone(),
+                owner: owner.clone(),
+                redirect_kind: *kind,
+            })
+            .collect();
+        records.sort_by(|a, b| a.short_name.cmp(&b.short_name));
+        Ok(records)
+    }
+
+    async fn set_import_cursor(&self, cursor: Option<&str>) -> Result<()> {
+        let mut slot = self.cursor.write().map_err(|e| StorageError::InternalError(e.to_string()))?;
+        *slot = cursor.map(str::to_string);
+        Ok(())
+    }
+
+    async fn import_cursor(&self) -> Result<Option<String>> {
+        let slot = self.cursor.read().map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(slot.clone())
+    }
+}
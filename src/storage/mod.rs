@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+pub type Result<T> = std::result::Result<T, StorageError>;
+
+/// HTTP redirect semantics chosen per redirect by its creator. The wire form
+/// (form field / persisted value) is the snake_case name; `permanent` is the
+/// default so existing behaviour (308) is preserved when the field is absent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectKind {
+    /// 301 Moved Permanently
+    Moved,
+    /// 308 Permanent Redirect
+    Permanent,
+    /// 307 Temporary Redirect
+    Temporary,
+    /// 303 See Other
+    SeeOther,
+}
+
+impl Default for RedirectKind {
+    fn default() -> Self {
+        RedirectKind::Permanent
+    }
+}
+
+impl RedirectKind {
+    /// Stable string used when persisting the kind in a backend.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RedirectKind::Moved => "moved",
+            RedirectKind::Permanent => "permanent",
+            RedirectKind::Temporary => "temporary",
+            RedirectKind::SeeOther => "see_other",
+        }
+    }
+
+    /// Parse a persisted value back into a kind, defaulting to `permanent`
+    /// for anything unrecognised (e.g. rows written before this field).
+    pub fn from_stored(s: &str) -> Self {
+        match s {
+            "moved" => RedirectKind::Moved,
+            "temporary" => RedirectKind::Temporary,
+            "see_other" => RedirectKind::SeeOther,
+            _ => RedirectKind::Permanent,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StorageError {
+    NotFound,
+    AlreadyExists,
+    InternalError(String),
+}
+
+/// One redirect as carried over the bulk import/export wire (newline-delimited
+/// JSON). Mirrors the persisted tuple so a round-trip is lossless.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedirectRecord {
+    pub short_name: String,
+    pub url: String,
+    pub owner: String,
+    #[serde(default)]
+    pub redirect_kind: RedirectKind,
+}
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn lookup(&self, id: &str) -> Result<(String, RedirectKind)>;
+    async fn store(&self, id: &str, url: &str, owner: &str, kind: RedirectKind) -> Result<()>;
+    async fn list_by_owner(&self, owner: &str) -> Result<Vec<(String, String)>>;
+    /// Increment the hit counter for `id` by `n` in a single write. Called
+    /// from the background aggregation task with a batched count, never from
+    /// the redirect hot path.
+    async fn record_hits(&self, id: &str, n: u64) -> Result<()>;
+    /// Return the accumulated hit count for `id` (0 if never hit).
+    async fn stats(&self, id: &str) -> Result<u64>;
+    /// Enumerate every stored redirect, ordered by `short_name` so bulk export
+    /// and resumable import see a stable sequence.
+    async fn iter_all(&self) -> Result<Vec<RedirectRecord>>;
+    /// Persist the continuation cursor (last successfully imported
+    /// `short_name`) so an interrupted import can resume. `None` clears it.
+    async fn set_import_cursor(&self, cursor: Option<&str>) -> Result<()>;
+    /// Load the continuation cursor saved by [`set_import_cursor`].
+    async fn import_cursor(&self) -> Result<Option<String>>;
+}
+
+mod memory;
+pub use memory::InMemoryStorage;
+
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresStorage;
+
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "redis")]
+pub use redis::RedisStorage;
+
+use std::sync::Arc;
+
+/// Build the storage backend selected via the `REDIRECTOR_BACKEND` env var
+/// (or the `--backend` CLI flag, which `main` forwards here). Defaults to the
+/// in-memory backend so a plain `cargo run` still works with no extra setup.
+pub async fn from_backend(name: &str) -> Result<Arc<dyn Storage>> {
+    match name {
+        "memory" => Ok(Arc::new(InMemoryStorage::new())),
+        #[cfg(feature = "postgres")]
+        "postgres" => {
+            let url = std::env::var("DATABASE_URL")
+                .map_err(|_| StorageError::InternalError("DATABASE_URL is not set".to_string()))?;
+            Ok(Arc::new(PostgresStorage::connect(&url).await?))
+        }
+        #[cfg(feature = "redis")]
+        "redis" => {
+            let url = std::env::var("REDIS_URL")
+                .map_err(|_| StorageError::InternalError("REDIS_URL is not set".to_string()))?;
+            Ok(Arc::new(RedisStorage::connect(&url).await?))
+        }
+        other => Err(StorageError::InternalError(format!(
+            "unknown storage backend: {other}"
+        ))),
+    }
+}
@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+
+use super::{RedirectKind, RedirectRecord, Result, Storage, StorageError};
+
+/// Postgres-backed storage. Redirects live in a `redirects` table whose
+/// primary key is `short_name`, so a duplicate `store` surfaces as a unique
+/// constraint violation which we map back onto [`StorageError::AlreadyExists`].
+pub struct PostgresStorage {
+    pool: PgPool,
+}
+
+impl PostgresStorage {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .connect(url)
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS redirects (\
+                short_name TEXT PRIMARY KEY, \
+                url TEXT NOT NULL, \
+                owner TEXT NOT NULL, \
+                kind TEXT NOT NULL DEFAULT 'permanent')",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS redirect_hits (\
+                short_name TEXT PRIMARY KEY, \
+                hits BIGINT NOT NULL DEFAULT 0)",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS import_state (\
+                id INT PRIMARY KEY, \
+                cursor TEXT)",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(PostgresStorage { pool })
+    }
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    async fn lookup(&self, id: &str) -> Result<(String, RedirectKind)> {
+        let row: Option<(String, String)> =
+            sqlx::query_as("SELECT url, kind FROM redirects WHERE short_name = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        row.map(|(url, kind)| (url, RedirectKind::from_stored(&kind)))
+            .ok_or(StorageError::NotFound)
+    }
+
+    async fn store(&self, id: &str, url: &str, owner: &str, kind: RedirectKind) -> Result<()> {
+        let result = sqlx::query(
+            "INSERT INTO redirects (short_name, url, owner, kind) VALUES ($1, $2, $3, $4)",
+        )
+            .bind(id)
+            .bind(url)
+            .bind(owner)
+            .bind(kind.as_str())
+            .execute(&self.pool)
+            .await;
+        match result {
+            Ok(_) => Ok(()),
+            // 23505 = unique_violation on the short_name primary key
+            Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("23505") => {
+                Err(StorageError::AlreadyExists)
+            }
+            Err(e) => Err(StorageError::InternalError(e.to_string())),
+        }
+    }
+
+    async fn list_by_owner(&self, owner: &str) -> Result<Vec<(String, String)>> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT short_name, url FROM redirects WHERE owner = $1")
+                .bind(owner)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(rows)
+    }
+
+    async fn record_hits(&self, id: &str, n: u64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO redirect_hits (short_name, hits) VALUES ($1, $2) \
+             ON CONFLICT (short_name) DO UPDATE SET hits = redirect_hits.hits + $2",
+        )
+        .bind(id)
+        .bind(n as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn stats(&self, id: &str) -> Result<u64> {
+        let row: Option<(i64,)> =
+            sqlx::query_as("SELECT hits FROM redirect_hits WHERE short_name = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(row.map(|(hits,)| hits.max(0) as u64).unwrap_or(0))
+    }
+
+    async fn iter_all(&self) -> Result<Vec<RedirectRecord>> {
+        let rows: Vec<(String, String, String, String)> =
+            sqlx::query_as("SELECT short_name, url, owner, kind FROM redirects ORDER BY short_name")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(rows
+            .into_iter()
+            .map(|(short_name, url, owner, kind)| RedirectRecord {
+                short_name,
+                url,
+                owner,
+                redirect_kind: RedirectKind::from_stored(&kind),
+            })
+            .collect())
+    }
+
+    async fn set_import_cursor(&self, cursor: Option<&str>) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO import_state (id, cursor) VALUES (0, $1) \
+             ON CONFLICT (id) DO UPDATE SET cursor = $1",
+        )
+        .bind(cursor)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn import_cursor(&self) -> Result<Option<String>> {
+        let row: Option<(Option<String>,)> =
+            sqlx::query_as("SELECT cursor FROM import_state WHERE id = 0")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(row.and_then(|(cursor,)| cursor))
+    }
+}
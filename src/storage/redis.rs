@@ -0,0 +1,181 @@
+use async_trait::async_trait;
+use redis::AsyncCommands;
+
+use super::{RedirectKind, RedirectRecord, Result, Storage, StorageError};
+
+/// Redis-backed storage. Each redirect is a string keyed by `redirect:<id>`
+/// whose value is `owner\nurl\nkind`; `store` uses `SET ... NX` so a key that
+/// already exists yields a nil reply, which we translate into
+/// [`StorageError::AlreadyExists`].
+pub struct RedisStorage {
+    client: redis::Client,
+}
+
+impl RedisStorage {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client =
+            redis::Client::open(url).map_err(|e| StorageError::InternalError(e.to_string()))?;
+        // Eagerly validate connectivity so a bad URL fails at startup.
+        client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(RedisStorage { client })
+    }
+
+    fn key(id: &str) -> String {
+        format!("redirect:{id}")
+    }
+
+    async fn conn(&self) -> Result<redis::aio::MultiplexedConnection> {
+        self.client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl Storage for RedisStorage {
+    async fn lookup(&self, id: &str) -> Result<(String, RedirectKind)> {
+        let mut conn = self.conn().await?;
+        let value: Option<String> = conn
+            .get(Self::key(id))
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        match value {
+            // Stored as `owner\nurl\nkind`.
+            Some(v) => {
+                let mut parts = v.splitn(3, '\n');
+                let _owner = parts.next().unwrap_or_default();
+                let url = parts.next().unwrap_or_default().to_string();
+                let kind = parts.next().map(RedirectKind::from_stored).unwrap_or_default();
+                Ok((url, kind))
+            }
+            None => Err(StorageError::NotFound),
+        }
+    }
+
+    async fn store(&self, id: &str, url: &str, owner: &str, kind: RedirectKind) -> Result<()> {
+        let mut conn = self.conn().await?;
+        let value = format!("{owner}\n{url}\n{}", kind.as_str());
+        let set: Option<String> = redis::cmd("SET")
+            .arg(Self::key(id))
+            .arg(value)
+            .arg("NX")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        match set {
+            Some(_) => Ok(()),
+            None => Err(StorageError::AlreadyExists),
+        }
+    }
+
+    async fn list_by_owner(&self, owner: &str) -> Result<Vec<(String, String)>> {
+        let mut conn = self.conn().await?;
+        let mut out = Vec::new();
+        let mut iter: redis::AsyncIter<String> = conn
+            .scan_match("redirect:*")
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        for key in keys {
+            let value: Option<String> = conn
+                .get(&key)
+                .await
+                .map_err(|e| StorageError::InternalError(e.to_string()))?;
+            if let Some(v) = value {
+                let mut parts = v.splitn(3, '\n');
+                let o = parts.next().unwrap_or_default();
+                let url = parts.next().unwrap_or_default();
+                if o == owner {
+                    let id = key.strip_prefix("redirect:").unwrap_or(&key);
+                    out.push((id.to_string(), url.to_string()));
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    async fn record_hits(&self, id: &str, n: u64) -> Result<()> {
+        let mut conn = self.conn().await?;
+        let _: i64 = conn
+            .incr(format!("hits:{id}"), n)
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn stats(&self, id: &str) -> Result<u64> {
+        let mut conn = self.conn().await?;
+        let count: Option<u64> = conn
+            .get(format!("hits:{id}"))
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        Ok(count.unwrap_or(0))
+    }
+
+    async fn iter_all(&self) -> Result<Vec<RedirectRecord>> {
+        let mut conn = self.conn().await?;
+        let mut iter: redis::AsyncIter<String> = conn
+            .scan_match("redirect:*")
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        let mut records = Vec::new();
+        for key in keys {
+            let value: Option<String> = conn
+                .get(&key)
+                .await
+                .map_err(|e| StorageError::InternalError(e.to_string()))?;
+            if let Some(v) = value {
+                let mut parts = v.splitn(3, '\n');
+                let owner = parts.next().unwrap_or_default().to_string();
+                let url = parts.next().unwrap_or_default().to_string();
+                let kind = parts.next().map(RedirectKind::from_stored).unwrap_or_default();
+                let id = key.strip_prefix("redirect:").unwrap_or(&key).to_string();
+                records.push(RedirectRecord {
+                    short_name: id,
+                    url,
+                    owner,
+                    redirect_kind: kind,
+                });
+            }
+        }
+        records.sort_by(|a, b| a.short_name.cmp(&b.short_name));
+        Ok(records)
+    }
+
+    async fn set_import_cursor(&self, cursor: Option<&str>) -> Result<()> {
+        let mut conn = self.conn().await?;
+        match cursor {
+            Some(c) => {
+                let _: () = conn
+                    .set("import:cursor", c)
+                    .await
+                    .map_err(|e| StorageError::InternalError(e.to_string()))?;
+            }
+            None => {
+                let _: () = conn
+                    .del("import:cursor")
+                    .await
+                    .map_err(|e| StorageError::InternalError(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn import_cursor(&self) -> Result<Option<String>> {
+        let mut conn = self.conn().await?;
+        conn.get("import:cursor")
+            .await
+            .map_err(|e| StorageError::InternalError(e.to_string()))
+    }
+}
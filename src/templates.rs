@@ -0,0 +1,46 @@
+use handlebars::Handlebars;
+use serde::Serialize;
+
+/// Build the Handlebars registry used for every rendered view. Templates are
+/// embedded at compile time via `include_str!`, so the binary carries its own
+/// views and has no runtime dependency on the working directory.
+pub fn registry() -> Handlebars<'static> {
+    let mut hb = Handlebars::new();
+    hb.register_template_string("index", include_str!("templates/index.hbs"))
+        .expect("index template is valid handlebars");
+    hb.register_template_string("error", include_str!("templates/error.hbs"))
+        .expect("error template is valid handlebars");
+    hb.register_template_string("redirects", include_str!("templates/redirects.hbs"))
+        .expect("redirects template is valid handlebars");
+    hb
+}
+
+/// Context rendered by the `index` template. Both the bare landing page and
+/// the post-`/create` confirmation/error banner share this shape; `message`
+/// is `None` for a plain page load.
+#[derive(Serialize)]
+pub struct IndexContext {
+    pub message: Option<String>,
+    pub success: bool,
+}
+
+/// Context rendered by the `error` template, used as the fallback page for
+/// storage failures and unmatched routes alike.
+#[derive(Serialize)]
+pub struct ErrorContext {
+    pub message: String,
+}
+
+/// One row rendered by the `redirects` template.
+#[derive(Serialize)]
+pub struct RedirectEntry {
+    pub short_name: String,
+    pub url: String,
+}
+
+/// Context rendered by the `redirects` template: the authenticated owner's
+/// own short links.
+#[derive(Serialize)]
+pub struct RedirectsContext {
+    pub redirects: Vec<RedirectEntry>,
+}